@@ -0,0 +1,214 @@
+use crate::*;
+
+/// Translates one rustc `BasicBlockData` into a MiniRust `BasicBlock`, recording the source
+/// span of every statement and the terminator as it goes.
+pub fn translate_bb(
+    fcx: &mut FnCtxt<'_, '_>,
+    name: BbName,
+    data: &rs::BasicBlockData<'_>,
+) -> BasicBlock {
+    let mut statements = List::default();
+    for (i, stmt) in data.statements.iter().enumerate() {
+        let point = fcx.record_span(name, Some(Int::from(i)), stmt.source_info.span);
+        match translate_statement(fcx, point, stmt) {
+            Some(statement) => statements.push(statement),
+            // The statement reads a constant of an uninhabited type, so this program point can
+            // never actually be reached -- the rest of the block is dead code we don't need (and
+            // may not even be able) to translate.
+            None => return BasicBlock { statements, terminator: Terminator::Unreachable },
+        }
+    }
+
+    let terminator_point = fcx.record_span(name, None, data.terminator().source_info.span);
+    let terminator =
+        translate_terminator(fcx, terminator_point, data.terminator(), &mut statements);
+
+    BasicBlock { statements, terminator }
+}
+
+/// Translates one statement, or returns `None` if its program point turned out to be
+/// unreachable (see [`translate_bb`]).
+fn translate_statement(
+    fcx: &mut FnCtxt<'_, '_>,
+    point: ProgramPoint,
+    stmt: &rs::Statement<'_>,
+) -> Option<Statement> {
+    let span = stmt.source_info.span;
+    match &stmt.kind {
+        rs::StatementKind::Assign(box (place, rvalue)) => {
+            let destination = translate_place(fcx, place);
+            let value = translate_rvalue(fcx, point, rvalue)?;
+            Some(Statement::Assign { destination, source: value })
+        }
+        rs::StatementKind::StorageLive(local) => {
+            Some(Statement::StorageLive(local_name(fcx, *local)))
+        }
+        rs::StatementKind::StorageDead(local) => {
+            Some(Statement::StorageDead(local_name(fcx, *local)))
+        }
+        _ => rs::span_bug!(span, "unsupported statement in translator: {:?}", stmt.kind),
+    }
+}
+
+fn translate_terminator(
+    fcx: &mut FnCtxt<'_, '_>,
+    point: ProgramPoint,
+    term: &rs::Terminator<'_>,
+    statements: &mut List<Statement>,
+) -> Terminator {
+    let span = term.source_info.span;
+    match &term.kind {
+        rs::TerminatorKind::Return => Terminator::Return,
+        rs::TerminatorKind::Goto { target } => Terminator::Goto(bb_name(*target)),
+        rs::TerminatorKind::Unreachable => Terminator::Unreachable,
+        rs::TerminatorKind::SwitchInt { discr, targets } => {
+            // An unreachable discriminant makes the switch itself unreachable, same as an
+            // unreachable statement earlier in the block (see `translate_bb`).
+            let Some(value) = translate_operand(fcx, point, discr) else {
+                return Terminator::Unreachable;
+            };
+            let cases =
+                targets.iter().map(|(val, target)| (Int::from(val), bb_name(target))).collect();
+            let fallback = bb_name(targets.otherwise());
+            Terminator::Switch { value, cases, fallback }
+        }
+        rs::TerminatorKind::Call { func, args, destination, target, .. } => {
+            translate_call(fcx, point, span, func, args, destination, *target, statements)
+        }
+        _ => rs::span_bug!(span, "unsupported terminator in translator: {:?}", term.kind),
+    }
+}
+
+/// Lowers a `Call` terminator, consulting the [`shims`] registry before falling back to
+/// translating the callee's own MIR body into an ordinary MiniRust `Call`.
+fn translate_call(
+    fcx: &mut FnCtxt<'_, '_>,
+    point: ProgramPoint,
+    span: rs::Span,
+    func: &rs::Operand<'_>,
+    args: &[rs::Spanned<rs::Operand<'_>>],
+    destination: &rs::Place<'_>,
+    target: Option<rs::BasicBlock>,
+    statements: &mut List<Statement>,
+) -> Terminator {
+    // An unreachable argument makes the call itself unreachable, same as in `translate_bb`.
+    let Some(arg_values) =
+        args.iter().map(|arg| translate_operand(fcx, point, &arg.node)).collect::<Option<Vec<_>>>()
+    else {
+        return Terminator::Unreachable;
+    };
+    let arg_types: Vec<Option<rs::Ty<'_>>> =
+        args.iter().map(|arg| operand_rustc_ty(fcx, &arg.node)).collect();
+    let next_block = target.map(bb_name);
+    let ret_place = translate_place(fcx, destination);
+
+    if let Some((def_path, generic_args)) = resolve_def_path(fcx, func) {
+        if let Some(shim) = shims::lookup_shim(&def_path) {
+            return match shim {
+                shims::Shim::Inline(inline) => {
+                    let value = inline(fcx, &def_path, &arg_values, generic_args);
+                    statements.push(Statement::Assign { destination: ret_place, source: value });
+                    Terminator::Goto(
+                        next_block.expect("inline shims always return to their caller"),
+                    )
+                }
+                shims::Shim::Body(body) => {
+                    let function = body(fcx, &def_path, &arg_values, &arg_types);
+                    let callee = fcx.ctx.declare_function(function);
+                    emit_call(callee, arg_values, Some(ret_place), next_block)
+                }
+            };
+        }
+    }
+
+    let instance = resolve_instance(fcx, func)
+        .unwrap_or_else(|| rs::span_bug!(span, "call to a callee that is not a concrete function"));
+    let callee = fcx.ctx.translate_instance(instance);
+    emit_call(callee, arg_values, Some(ret_place), next_block)
+}
+
+/// Builds the `Call` terminator shared by `Shim::Body` splices and ordinary callee lowering.
+fn emit_call(
+    callee: FnName,
+    arguments: Vec<ValueExpr>,
+    ret: Option<PlaceExpr>,
+    next_block: Option<BbName>,
+) -> Terminator {
+    Terminator::Call {
+        callee: ValueExpr::Constant(Constant::FnPointer(callee), Type::Ptr(PtrType::FnPtr)),
+        calling_convention: CallingConvention::Rust,
+        arguments: List::from_iter(arguments.into_iter().map(ArgumentExpr::ByValue)),
+        ret,
+        next_block,
+    }
+}
+
+/// Resolves the `smir` def path of a call's callee, together with its own monomorphized
+/// generics, if it refers to a concrete function.
+fn resolve_def_path<'tcx>(
+    fcx: &FnCtxt<'_, 'tcx>,
+    func: &rs::Operand<'tcx>,
+) -> Option<(String, rs::GenericArgsRef<'tcx>)> {
+    let rs::Operand::Constant(c) = func else { return None };
+    match c.const_.ty().kind() {
+        rs::TyKind::FnDef(def_id, args) => Some((fcx.ctx.tcx.def_path_str(*def_id), args)),
+        _ => None,
+    }
+}
+
+/// Resolves a call's callee operand to the concrete, monomorphized `rs::Instance` it invokes --
+/// the callee whose own MIR body should be translated when no shim claims the call.
+fn resolve_instance<'tcx>(
+    fcx: &FnCtxt<'_, 'tcx>,
+    func: &rs::Operand<'tcx>,
+) -> Option<rs::Instance<'tcx>> {
+    let rs::Operand::Constant(c) = func else { return None };
+    let rs::TyKind::FnDef(def_id, args) = c.const_.ty().kind() else { return None };
+    rs::Instance::resolve(fcx.ctx.tcx, rs::ParamEnv::reveal_all(), *def_id, args).ok().flatten()
+}
+
+/// Recovers the rustc type of a call argument, when it's cheap to do without a full place
+/// analysis -- a constant always carries its own type, and a bare (unprojected) local's type
+/// was already recorded when `function` registered it. Shims that need more than a `ValueExpr`
+/// can tell them apart (e.g. `thread::spawn` needing the closure's concrete type) use this.
+fn operand_rustc_ty<'tcx>(fcx: &FnCtxt<'_, 'tcx>, op: &rs::Operand<'tcx>) -> Option<rs::Ty<'tcx>> {
+    match op {
+        rs::Operand::Constant(c) => Some(c.const_.ty()),
+        rs::Operand::Copy(place) | rs::Operand::Move(place) if place.projection.is_empty() => {
+            let name = fcx.locals.get(&place.local)?;
+            fcx.local_rs_types.get(name).copied()
+        }
+        _ => None,
+    }
+}
+
+fn bb_name(bb: rs::BasicBlock) -> BbName {
+    BbName(Int::from(bb.as_usize()))
+}
+
+/// Looks up (or, the first time, allocates) the `LocalName` a rustc local translates to.
+///
+/// `function` pre-registers every local of the body before translating any block, so by the
+/// time `bb`/`rvalue` call this for a place appearing in a statement or terminator, it's always
+/// just a lookup.
+pub(crate) fn local_name(fcx: &mut FnCtxt<'_, '_>, local: rs::Local) -> LocalName {
+    let next = LocalName(Int::from(fcx.locals.len()));
+    *fcx.locals.entry(local).or_insert(next)
+}
+
+pub(crate) fn translate_place(fcx: &mut FnCtxt<'_, '_>, place: &rs::Place<'_>) -> PlaceExpr {
+    PlaceExpr::Local(local_name(fcx, place.local))
+}
+
+fn translate_operand(
+    fcx: &mut FnCtxt<'_, '_>,
+    point: ProgramPoint,
+    op: &rs::Operand<'_>,
+) -> Option<ValueExpr> {
+    match op {
+        rs::Operand::Copy(place) | rs::Operand::Move(place) => {
+            Some(ValueExpr::Load(translate_place(fcx, place)))
+        }
+        rs::Operand::Constant(c) => crate::constant::translate_constant(fcx, point, c),
+    }
+}
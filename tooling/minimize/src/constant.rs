@@ -0,0 +1,61 @@
+use crate::*;
+
+/// Lowers a MIR constant operand -- including inline `const { ... }` blocks, which reach the
+/// translator as unevaluated constants needing a const-eval pass first -- into a MiniRust
+/// `ValueExpr`.
+///
+/// Returns `None` when the constant's own type is uninhabited: there is no value it could ever
+/// hold, so the program point holding it is unreachable rather than ill-formed or wrong --
+/// callers turn that into a `Terminator::Unreachable` instead of emitting a bogus value.
+pub fn translate_constant(
+    fcx: &mut FnCtxt<'_, '_>,
+    point: ProgramPoint,
+    c: &rs::ConstOperand<'_>,
+) -> Option<ValueExpr> {
+    let span = fcx.ctx.span_for(point).unwrap_or(c.span);
+    let tcx = fcx.ctx.tcx;
+
+    let val = match c.const_ {
+        // A `const { ... }` block (or any other not-yet-evaluated constant) is const-eval'd
+        // here, the same way a normal MIR constant operand already was by the time it reached
+        // us -- the translator never sees unevaluated consts otherwise.
+        rs::Const::Unevaluated(uneval, ty) => tcx
+            .const_eval_resolve(rs::ParamEnv::reveal_all(), uneval, span)
+            .unwrap_or_else(|_| rs::span_bug!(span, "failed to evaluate const block of type {ty}")),
+        rs::Const::Val(val, _) => val,
+        rs::Const::Ty(..) => rs::span_bug!(span, "unexpected unsubstituted type constant"),
+    };
+
+    translate_const_value(fcx, span, val, c.const_.ty())
+}
+
+fn translate_const_value(
+    fcx: &mut FnCtxt<'_, '_>,
+    span: rs::Span,
+    val: rs::ConstValue<'_>,
+    ty: rs::Ty<'_>,
+) -> Option<ValueExpr> {
+    if ty.is_never() {
+        return None;
+    }
+    if let rs::TyKind::Adt(adt, _) = ty.kind() {
+        if enums::is_uninhabited_enum(*adt, fcx.ctx.tcx) {
+            return None;
+        }
+    }
+    match val {
+        rs::ConstValue::Scalar(rs::Scalar::Int(int)) => {
+            let bits = int
+                .try_to_bits(int.size())
+                .unwrap_or_else(|_| rs::span_bug!(span, "non-integer scalar constant"));
+            match ty.kind() {
+                rs::TyKind::Adt(adt, _) if adt.is_enum() => {
+                    let discriminant = enums::int_from_bits(bits, *adt, fcx.ctx.tcx)?;
+                    Some(ValueExpr::Constant(Constant::Int(discriminant), translate_ty(ty)))
+                }
+                _ => Some(ValueExpr::Constant(Constant::Int(Int::from(bits)), translate_ty(ty))),
+            }
+        }
+        _ => rs::span_bug!(span, "unsupported constant value: {:?}", val),
+    }
+}
@@ -0,0 +1,203 @@
+use crate::*;
+
+/// A single `//@ ...` expectation directive found in a test's leading comment block.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Directive {
+    /// The program should run to completion without hitting UB, a deadlock or a leak.
+    RunPass,
+    /// The interpreter should stop without error (the general "nothing went wrong" case).
+    Stop,
+    /// The program should hit UB whose error message contains the given substring.
+    Ub(String),
+    /// The interpreter should reject the translated program as not well-formed MiniRust.
+    IllFormed,
+    /// The program should deadlock.
+    Deadlock,
+    /// The program should leak memory.
+    Leak,
+    /// The program should hit a data race.
+    DataRace,
+}
+
+impl Directive {
+    /// Parses a single directive from the text following `//@ `.
+    fn parse(rest: &str) -> Option<Directive> {
+        let rest = rest.trim();
+        if rest == "run-pass" {
+            Some(Directive::RunPass)
+        } else if rest == "stop" {
+            Some(Directive::Stop)
+        } else if rest == "ill-formed" {
+            Some(Directive::IllFormed)
+        } else if rest == "deadlock" {
+            Some(Directive::Deadlock)
+        } else if rest == "leak" {
+            Some(Directive::Leak)
+        } else if rest == "data-race" {
+            Some(Directive::DataRace)
+        } else if let Some(msg) = rest.strip_prefix("ub:") {
+            Some(Directive::Ub(msg.trim().to_string()))
+        } else {
+            None
+        }
+    }
+
+    /// Scans the leading comment block of `source` for `//@` directives, stopping at the
+    /// first line that is neither blank nor a `//`-comment -- so a directive-shaped comment
+    /// appearing later in the file, say inside `fn main`, is left alone rather than collected.
+    pub fn parse_all(source: &str) -> Vec<Directive> {
+        let mut directives = Vec::new();
+        for line in source.lines() {
+            let trimmed = line.trim();
+            if trimmed.is_empty() {
+                continue;
+            }
+            let Some(comment) = trimmed.strip_prefix("//") else { break };
+            if let Some(rest) = comment.strip_prefix('@') {
+                directives.extend(Directive::parse(rest));
+            }
+        }
+        directives
+    }
+}
+
+/// The outcome a test file's directives say `run_program` should produce.
+///
+/// A test has exactly one directive, the same way a compiletest test has exactly one mode: it
+/// describes the single way the program is expected to terminate, not a checklist to run down.
+#[derive(Debug)]
+pub struct Expectation {
+    directive: Directive,
+}
+
+impl Expectation {
+    /// Parses the expectation out of a test's source text.
+    ///
+    /// Fails if the source has no `//@` directive (compiletest rejects mode-less tests the same
+    /// way) or more than one (there is no sound way to check a program's single termination
+    /// against two different expected outcomes at once).
+    pub fn from_source(source: &str) -> Result<Expectation, String> {
+        let mut directives = Directive::parse_all(source);
+        match directives.len() {
+            0 => Err("no `//@` directive found; every test needs exactly one".to_string()),
+            1 => Ok(Expectation { directive: directives.remove(0) }),
+            n => Err(format!("found {n} `//@` directives, but a test may only have one")),
+        }
+    }
+
+    /// Compares the actual termination of the program against the parsed directive, returning
+    /// `Err` with a human-readable diff on mismatch.
+    pub fn check(&self, info: &TerminationInfo) -> Result<(), String> {
+        let matches = match (&self.directive, info) {
+            (Directive::RunPass, TerminationInfo::MachineStop) => true,
+            (Directive::Stop, TerminationInfo::MachineStop) => true,
+            (Directive::IllFormed, TerminationInfo::IllFormed(_)) => true,
+            (Directive::Deadlock, TerminationInfo::Deadlock) => true,
+            (Directive::Leak, TerminationInfo::MemoryLeak(_)) => true,
+            (Directive::DataRace, TerminationInfo::DataRace { .. }) => true,
+            (Directive::Ub(msg), TerminationInfo::Ub(err)) => {
+                err.get_internal().contains(msg.as_str())
+            }
+            _ => false,
+        };
+        if matches {
+            Ok(())
+        } else {
+            Err(format!(
+                "expected `{:?}`, but the program terminated with {}",
+                self.directive,
+                describe(info)
+            ))
+        }
+    }
+}
+
+/// Renders a `TerminationInfo` the way the expectation diff wants to show it to a human.
+fn describe(info: &TerminationInfo) -> String {
+    match info {
+        TerminationInfo::MachineStop => "a clean stop".to_string(),
+        TerminationInfo::IllFormed(_) => "an ill-formed-program error".to_string(),
+        TerminationInfo::Deadlock => "a deadlock".to_string(),
+        TerminationInfo::MemoryLeak(leaks) => {
+            format!("a memory leak ({} allocation(s))", leaks.len())
+        }
+        TerminationInfo::Ub(err) => format!("UB: {}", err.get_internal()),
+        TerminationInfo::DataRace { .. } => "a data race".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_all_collects_the_leading_at_directives() {
+        let source = "//@ run-pass\n// a plain comment\nfn main() {}\n";
+        assert_eq!(Directive::parse_all(source), vec![Directive::RunPass]);
+    }
+
+    #[test]
+    fn parse_all_stops_at_the_first_non_comment_line() {
+        let source = "fn main() {}\n//@ run-pass\n";
+        assert_eq!(Directive::parse_all(source), vec![]);
+    }
+
+    #[test]
+    fn parse_all_ignores_plain_comments() {
+        let source = "// just a comment\n//@ stop\n";
+        assert_eq!(Directive::parse_all(source), vec![Directive::Stop]);
+    }
+
+    #[test]
+    fn parse_all_parses_an_ub_directive_with_its_message() {
+        let source = "//@ ub: out-of-bounds pointer\n";
+        assert_eq!(
+            Directive::parse_all(source),
+            vec![Directive::Ub("out-of-bounds pointer".to_string())]
+        );
+    }
+
+    #[test]
+    fn parse_all_skips_unrecognized_directives() {
+        let source = "//@ not-a-real-directive\n//@ stop\n";
+        assert_eq!(Directive::parse_all(source), vec![Directive::Stop]);
+    }
+
+    #[test]
+    fn from_source_rejects_a_test_with_no_directive() {
+        assert!(Expectation::from_source("fn main() {}\n").is_err());
+    }
+
+    #[test]
+    fn from_source_rejects_a_test_with_more_than_one_directive() {
+        assert!(Expectation::from_source("//@ run-pass\n//@ stop\n").is_err());
+    }
+
+    #[test]
+    fn check_accepts_a_matching_termination() {
+        let expectation = Expectation::from_source("//@ stop\n").unwrap();
+        assert!(expectation.check(&TerminationInfo::MachineStop).is_ok());
+    }
+
+    #[test]
+    fn check_rejects_a_mismatched_termination() {
+        let expectation = Expectation::from_source("//@ run-pass\n").unwrap();
+        assert!(expectation.check(&TerminationInfo::Deadlock).is_err());
+    }
+
+    #[test]
+    fn check_matches_ill_formed_regardless_of_where_it_happened() {
+        let expectation = Expectation::from_source("//@ ill-formed\n").unwrap();
+        assert!(expectation.check(&TerminationInfo::IllFormed(None)).is_ok());
+    }
+
+    #[test]
+    fn check_matches_data_race() {
+        let expectation = Expectation::from_source("//@ data-race\n").unwrap();
+        let info = TerminationInfo::DataRace {
+            first: (FnName(Int::ZERO), BbName(Int::ZERO), None),
+            second: (FnName(Int::ZERO), BbName(Int::ZERO), None),
+        };
+        assert!(expectation.check(&info).is_ok());
+    }
+}
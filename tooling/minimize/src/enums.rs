@@ -0,0 +1,54 @@
+use crate::*;
+
+/// Decodes a discriminant's raw bit pattern into the index of the variant it selects.
+///
+/// The bits stored in the tag are the discriminant *value* (`enum E { A = 5, B = 10 }` stores
+/// `5` or `10`), which for an enum without explicit (or niche-optimized) discriminants happens
+/// to equal the variant's index, but in general does not -- so this looks the value up among
+/// the type's real discriminants instead of assuming the two coincide.
+///
+/// Returns `None` for an uninhabited enum: there is no valid discriminant for a type no value
+/// of which can ever exist, so no bit pattern should be decoded as selecting one of its
+/// (themselves uninhabited) variants.
+pub fn int_from_bits(bits: u128, adt: rs::AdtDef<'_>, tcx: rs::TyCtxt<'_>) -> Option<Int> {
+    if is_uninhabited_enum(adt, tcx) {
+        return None;
+    }
+    let (variant, _) = adt
+        .discriminants(tcx)
+        .find(|(_, discr)| discr.val == bits)
+        .unwrap_or_else(|| panic!("discriminant {bits} does not match any variant of {adt:?}"));
+    Some(Int::from(variant.as_usize()))
+}
+
+/// Whether no value of `adt` can ever exist: either it has no variants at all, or every one of
+/// its variants is itself uninhabited (e.g. `enum E { A(Never) }` has a variant, but that
+/// variant can never actually be constructed either).
+pub fn is_uninhabited_enum(adt: rs::AdtDef<'_>, tcx: rs::TyCtxt<'_>) -> bool {
+    adt.is_enum() && adt.variants().iter().all(|variant| is_uninhabited_variant(variant, tcx))
+}
+
+fn is_uninhabited_variant(variant: &rs::VariantDef, tcx: rs::TyCtxt<'_>) -> bool {
+    variant.fields.iter().any(|field| is_uninhabited_ty(tcx.type_of(field.did).skip_binder(), tcx))
+}
+
+/// A conservative, syntactic inhabitedness check: only `!` and enums/structs built out of it are
+/// recognized as uninhabited, which covers the common cases without needing a full privacy- and
+/// generic-substitution-aware layout query.
+///
+/// FIXME: a field's type is taken from `tcx.type_of(field.did)` with its generics left
+/// unsubstituted, so a generic wrapper like `struct Wrapper<T>(T);` instantiated as
+/// `Wrapper<!>` is never recognized as uninhabited here -- the field type seen is the bare
+/// parameter `T`, not `!`. When that happens, `int_from_bits` can still reach its
+/// `panic!("discriminant ... does not match any variant")` on such a type's constants instead
+/// of returning `None`.
+fn is_uninhabited_ty<'tcx>(ty: rs::Ty<'tcx>, tcx: rs::TyCtxt<'tcx>) -> bool {
+    match ty.kind() {
+        rs::TyKind::Never => true,
+        rs::TyKind::Adt(adt, _) if adt.is_enum() => is_uninhabited_enum(*adt, tcx),
+        rs::TyKind::Adt(adt, _) if adt.is_struct() => adt
+            .all_fields()
+            .any(|field| is_uninhabited_ty(tcx.type_of(field.did).skip_binder(), tcx)),
+        _ => false,
+    }
+}
@@ -0,0 +1,88 @@
+use crate::*;
+
+/// Per-function translation state: the global [`Ctx`](crate::program::Ctx) plus everything
+/// that is scoped to the function currently being translated.
+pub struct FnCtxt<'cx, 'tcx> {
+    pub ctx: &'cx mut Ctx<'tcx>,
+    /// The MiniRust name of the function being translated.
+    pub name: FnName,
+    /// Maps rustc locals to the MiniRust locals they were translated to.
+    pub locals: HashMap<rs::Local, LocalName>,
+    /// The MiniRust type each translated local was declared with.
+    pub local_types: HashMap<LocalName, Type>,
+    /// The rustc type each translated local was declared with, for the rare cases (like
+    /// resolving a closure's `call_once` shim) that need the real `rs::Ty` back.
+    pub local_rs_types: HashMap<LocalName, rs::Ty<'tcx>>,
+}
+
+impl<'cx, 'tcx> FnCtxt<'cx, 'tcx> {
+    /// Builds the [`ProgramPoint`] for a statement (or, with `statement: None`, the
+    /// terminator) of `block`, and records the span it came from.
+    pub fn record_span(
+        &mut self,
+        block: BbName,
+        statement: Option<Int>,
+        span: rs::Span,
+    ) -> ProgramPoint {
+        let point = ProgramPoint { function: self.name, block, statement };
+        self.ctx.add_span(point, span);
+        point
+    }
+}
+
+/// Translates a single monomorphized function body into a MiniRust [`Function`].
+pub fn translate_function(ctx: &mut Ctx<'_>, name: FnName, body: &rs::Body<'_>) -> Function {
+    let mut fcx = FnCtxt {
+        ctx,
+        name,
+        locals: HashMap::new(),
+        local_types: HashMap::new(),
+        local_rs_types: HashMap::new(),
+    };
+
+    // Register every rustc local up front, in MIR's own numbering (`_0` is the return place,
+    // `_1..=_arg_count` are the arguments): translating the blocks below then only ever looks
+    // these names up, instead of inventing them lazily and losing track of their types.
+    for (local, decl) in body.local_decls.iter_enumerated() {
+        let local_name = crate::bb::local_name(&mut fcx, local);
+        fcx.local_types.insert(local_name, translate_ty(decl.ty));
+        fcx.local_rs_types.insert(local_name, decl.ty);
+    }
+
+    let mut blocks = Map::default();
+    for (bb, data) in body.basic_blocks.iter_enumerated() {
+        let bb_name = BbName(Int::from(bb.as_usize()));
+        blocks.insert(bb_name, translate_bb(&mut fcx, bb_name, data));
+    }
+
+    let ret = Some(crate::bb::local_name(&mut fcx, rs::Local::from_usize(0)));
+    let args = (1..=body.arg_count)
+        .map(|i| crate::bb::local_name(&mut fcx, rs::Local::from_usize(i)))
+        .collect();
+    let locals = fcx
+        .locals
+        .values()
+        .map(|local_name| (*local_name, fcx.local_types[local_name].clone()))
+        .collect();
+
+    Function { locals, args, ret, blocks, start: BbName(Int::from(0)) }
+}
+
+/// A stand-in function body used to reserve a `FnName` slot before its real translation is
+/// available, so a (mutually) recursive callee resolves back to that same name instead of
+/// being translated again. Never observed by the interpreter: a recursive caller may be handed
+/// the name while this placeholder is still in place, but
+/// [`Ctx::translate_instance`](crate::program::Ctx::translate_instance) overwrites it with the
+/// real function before translation of the crate finishes.
+pub(crate) fn placeholder_function() -> Function {
+    Function {
+        locals: Map::default(),
+        args: List::default(),
+        ret: None,
+        blocks: Map::from_iter([(
+            BbName(Int::ZERO),
+            BasicBlock { statements: List::default(), terminator: Terminator::Unreachable },
+        )]),
+        start: BbName(Int::ZERO),
+    }
+}
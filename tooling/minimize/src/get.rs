@@ -0,0 +1,63 @@
+use crate::*;
+
+use rustc_driver::Compilation;
+use rustc_interface::{interface, Queries};
+
+/// Drives the full minimize pipeline: runs the `rustc` frontend up through MIR, translates
+/// every monomorphized function reachable from `main` into MiniRust, and hands the result --
+/// together with the span table recorded during translation -- to `f`.
+pub fn get_mini(f: impl FnOnce(rs::TyCtxt<'_>, Program, HashMap<ProgramPoint, rs::Span>)) {
+    // `--dump` and `--check-expect` are the driver's own flags, not rustc's.
+    let args: Vec<String> =
+        std::env::args().filter(|arg| arg != "--dump" && arg != "--check-expect").collect();
+    let mut callbacks = Cb { f: Some(f) };
+    rustc_driver::RunCompiler::new(&args, &mut callbacks).run().unwrap();
+}
+
+struct Cb<F> {
+    f: Option<F>,
+}
+
+impl<F: FnOnce(rs::TyCtxt<'_>, Program, HashMap<ProgramPoint, rs::Span>)> rustc_driver::Callbacks
+    for Cb<F>
+{
+    fn after_analysis<'tcx>(
+        &mut self,
+        _compiler: &interface::Compiler,
+        queries: &'tcx Queries<'tcx>,
+    ) -> Compilation {
+        queries.global_ctxt().unwrap().enter(|tcx| {
+            let mut ctx = Ctx::new(tcx);
+            let program = translate_crate(&mut ctx);
+            let spans = ctx.into_spans();
+            if let Some(f) = self.f.take() {
+                f(tcx, program, spans);
+            }
+        });
+        Compilation::Stop
+    }
+}
+
+/// Translates every monomorphized item reachable from the crate's entry point.
+fn translate_crate(ctx: &mut Ctx<'_>) -> Program {
+    // The entry function's `DefId`, so the instance it monomorphizes to can be told apart from
+    // every other reachable item below -- `start` must point at that one, not merely at
+    // whichever instance happens to get translated first.
+    let entry_def_id = ctx.tcx.entry_fn(()).expect("crate has no entry point").0;
+    let mut start = None;
+
+    for instance in smir::collect_reachable_mono_items(ctx.tcx) {
+        let instance = smir::internal(ctx.tcx, instance);
+        // Goes through `translate_instance`, exactly like every callee reached from inside a
+        // function body, so a reachable item already translated as somebody else's callee (or
+        // in the middle of being translated, for a recursive one) is never translated -- and
+        // never assigned a name -- twice.
+        let name = ctx.translate_instance(instance);
+        if instance.def_id() == entry_def_id {
+            start = Some(name);
+        }
+    }
+
+    let start = start.expect("entry point was not among the reachable mono items");
+    Program { functions: ctx.functions.clone(), start }
+}
@@ -0,0 +1,42 @@
+use crate::*;
+
+/// Renders the leaked allocations the interpreter reported. Each allocation's call stack is a
+/// sequence of `FrameLoc`s with no source span of their own, so `format_location` re-derives one
+/// by looking the frame's function/block/statement up in the spans the translator recorded
+/// while lowering that function -- the same `spans` map `FnCtxt::record_span` fills in on `Ctx`.
+pub fn format_leaks(
+    tcx: rs::TyCtxt<'_>,
+    spans: &HashMap<ProgramPoint, rs::Span>,
+    leaks: &[LeakReport],
+) -> String {
+    let mut msg = format!(
+        "program leaked memory ({} allocation{} never freed)",
+        leaks.len(),
+        if leaks.len() == 1 { "" } else { "s" }
+    );
+    for leak in leaks {
+        msg += &format!(
+            "\n  - alloc{} ({} bytes, align {}), allocated at:",
+            leak.id.0,
+            leak.size.bytes(),
+            leak.align.bytes()
+        );
+        for frame in &leak.allocated_at {
+            msg += &format!("\n      in {}{}", frame.function, format_location(tcx, spans, frame));
+        }
+    }
+    msg
+}
+
+fn format_location(
+    tcx: rs::TyCtxt<'_>,
+    spans: &HashMap<ProgramPoint, rs::Span>,
+    frame: &FrameLoc,
+) -> String {
+    let point =
+        ProgramPoint { function: frame.function, block: frame.block, statement: frame.statement };
+    match spans.get(&point) {
+        Some(span) => format!(" at {}", tcx.sess.source_map().span_to_diagnostic_string(*span)),
+        None => String::new(),
+    }
+}
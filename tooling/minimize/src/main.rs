@@ -63,6 +63,8 @@ mod bb;
 
 mod rvalue;
 
+mod shims;
+
 mod constant;
 
 mod get;
@@ -74,21 +76,77 @@ use chunks::calc_chunks;
 mod enums;
 use enums::int_from_bits;
 
+mod directives;
+use directives::Expectation;
+
+mod leak_report;
+use leak_report::format_leaks;
+
 use std::collections::HashMap;
 
 fn main() {
-    get_mini(|tcx, prog| {
-        let dump = std::env::args().skip(1).any(|x| x == "--dump");
+    let args: Vec<String> = std::env::args().collect();
+    let dump = args.iter().any(|x| x == "--dump");
+    // `--check-expect` switches the driver into compiletest-style mode: instead of printing a
+    // diagnostic on anything other than a clean stop, it compares the actual `TerminationInfo`
+    // against the `//@ ...` directives found at the top of the input file.
+    let check_expect = args.iter().any(|x| x == "--check-expect");
+
+    get_mini(|tcx, prog, spans| {
         if dump {
             dump_program(prog);
-        } else {
-            match run_program(prog) {
-                TerminationInfo::IllFormed =>
-                    tcx.dcx().fatal("ERR: program not well-formed (this is a bug in minimize)"),
-                TerminationInfo::MachineStop => { /* silent exit. */ }
-                TerminationInfo::Ub(err) => tcx.dcx().fatal(format!("UB: {}", err.get_internal())),
-                TerminationInfo::Deadlock => tcx.dcx().fatal("program dead-locked"),
-                TerminationInfo::MemoryLeak => tcx.dcx().fatal("program leaked memory"),
+            return;
+        }
+
+        let result = run_program(prog);
+        // Resolves a program point reported by the interpreter back to the real source span
+        // it was translated from, so diagnostics can underline the offending construct
+        // instead of aborting anonymously.
+        let span_of = |point: Option<(FnName, BbName, Option<Int>)>| {
+            point.and_then(|point| spans.get(&ProgramPoint::from(point))).copied()
+        };
+
+        if check_expect {
+            let source_path = args.last().expect("expected a source file argument");
+            let source = std::fs::read_to_string(source_path).unwrap_or_else(|err| {
+                tcx.dcx().fatal(format!("could not read {source_path}: {err}"))
+            });
+            let expectation = Expectation::from_source(&source)
+                .unwrap_or_else(|err| tcx.dcx().fatal(format!("{source_path}: {err}")));
+            if let Err(diff) = expectation.check(&result) {
+                tcx.dcx().fatal(format!("expectation mismatch in {source_path}:\n{diff}"));
+            }
+            return;
+        }
+
+        match result {
+            TerminationInfo::IllFormed(at) => {
+                let msg = "ERR: program not well-formed (this is a bug in minimize)";
+                match span_of(at) {
+                    Some(span) => tcx.dcx().span_fatal(span, msg),
+                    None => tcx.dcx().fatal(msg),
+                }
+            }
+            TerminationInfo::MachineStop => { /* silent exit. */ }
+            TerminationInfo::Ub(err) => {
+                let msg = format!("UB: {}", err.get_internal());
+                match span_of(err.location()) {
+                    Some(span) => tcx.dcx().span_fatal(span, msg),
+                    None => tcx.dcx().fatal(msg),
+                }
+            }
+            TerminationInfo::Deadlock => tcx.dcx().fatal("program dead-locked"),
+            TerminationInfo::MemoryLeak(leaks) => tcx.dcx().fatal(format_leaks(tcx, &spans, &leaks)),
+            TerminationInfo::DataRace { first, second } => {
+                let msg = "ERR: data race: two unsynchronized accesses to the same location, \
+                           at least one of which is a write";
+                match (span_of(Some(first)), span_of(Some(second))) {
+                    (Some(first), Some(second)) => {
+                        tcx.dcx().span_fatal(vec![first, second], msg)
+                    }
+                    (Some(span), None) | (None, Some(span)) => tcx.dcx().span_fatal(span, msg),
+                    (None, None) => tcx.dcx().fatal(msg),
+                }
             }
         }
     });
@@ -0,0 +1,89 @@
+use crate::*;
+
+/// Identifies a single MiniRust statement or terminator within the program being built.
+///
+/// This has no meaning to the MiniRust spec itself -- it is purely a bookkeeping device for
+/// the translator's own diagnostics (span attribution, leak reports, ...).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ProgramPoint {
+    pub function: FnName,
+    pub block: BbName,
+    /// `None` identifies the terminator of `block`, `Some(i)` identifies statement `i`.
+    pub statement: Option<Int>,
+}
+
+/// Translation-wide state threaded through `function`, `bb`, `rvalue`, ...
+pub struct Ctx<'tcx> {
+    pub tcx: rs::TyCtxt<'tcx>,
+    /// The MiniRust functions translated so far.
+    pub functions: Map<FnName, Function>,
+    /// Maps each translated MiniRust program point back to the `rs::Span` it came from, so
+    /// that UB and ill-formedness diagnostics can point at a real source location instead of
+    /// aborting anonymously.
+    pub spans: HashMap<ProgramPoint, rs::Span>,
+    /// The `FnName` each concrete callee `rs::Instance` has already been translated to, so a
+    /// function reached from more than one call site -- or (mutually) recursively from itself
+    /// -- is only translated, and only appears in the output program, once.
+    translated: HashMap<rs::Instance<'tcx>, FnName>,
+}
+
+impl From<(FnName, BbName, Option<Int>)> for ProgramPoint {
+    fn from((function, block, statement): (FnName, BbName, Option<Int>)) -> Self {
+        ProgramPoint { function, block, statement }
+    }
+}
+
+impl<'tcx> Ctx<'tcx> {
+    pub fn new(tcx: rs::TyCtxt<'tcx>) -> Self {
+        Ctx {
+            tcx,
+            functions: Default::default(),
+            spans: HashMap::new(),
+            translated: HashMap::new(),
+        }
+    }
+
+    /// Records that `point` was translated from `span`, for later diagnostics.
+    pub fn add_span(&mut self, point: ProgramPoint, span: rs::Span) {
+        self.spans.insert(point, span);
+    }
+
+    /// Looks up the source span a previously-translated program point came from.
+    pub fn span_for(&self, point: ProgramPoint) -> Option<rs::Span> {
+        self.spans.get(&point).copied()
+    }
+
+    /// Hands back the span table accumulated during translation, so the driver can attribute
+    /// UB and ill-formedness diagnostics to a real source location.
+    pub fn into_spans(self) -> HashMap<ProgramPoint, rs::Span> {
+        self.spans
+    }
+
+    /// Registers an already-built function (e.g. a [`shims::Shim::Body`](crate::shims::Shim)
+    /// splice) under a fresh name.
+    pub fn declare_function(&mut self, function: Function) -> FnName {
+        let name = FnName(Int::from(self.functions.len()));
+        self.functions.insert(name, function);
+        name
+    }
+
+    /// Translates `instance`'s MIR body into a MiniRust function and returns the name it was
+    /// registered under, translating it only the first time it is reached.
+    pub fn translate_instance(&mut self, instance: rs::Instance<'tcx>) -> FnName {
+        if let Some(&name) = self.translated.get(&instance) {
+            return name;
+        }
+
+        // Reserve the name, and a placeholder body, before translating: a (mutually) recursive
+        // callee looks itself up in `translated` while its own body is still being translated,
+        // and must find this same name rather than translating itself again.
+        let name = FnName(Int::from(self.functions.len()));
+        self.translated.insert(instance, name);
+        self.functions.insert(name, crate::function::placeholder_function());
+
+        let body = self.tcx.instance_mir(instance.def);
+        let function = crate::function::translate_function(self, name, body);
+        self.functions.insert(name, function);
+        name
+    }
+}
@@ -0,0 +1,54 @@
+use crate::*;
+
+/// Translates an rvalue appearing at `point` (already recorded in `fcx.ctx.spans`) into a
+/// MiniRust `ValueExpr`.
+///
+/// Returns `None` when a constant operand makes `rvalue` itself impossible to evaluate (see
+/// [`crate::constant::translate_constant`]); the caller drops the rest of the block in favor of
+/// `Terminator::Unreachable`.
+pub fn translate_rvalue(
+    fcx: &mut FnCtxt<'_, '_>,
+    point: ProgramPoint,
+    rvalue: &rs::Rvalue<'_>,
+) -> Option<ValueExpr> {
+    let span = fcx.ctx.span_for(point).expect("rvalue's program point was just recorded by bb.rs");
+    match rvalue {
+        rs::Rvalue::Use(op) => translate_operand_value(fcx, point, op),
+        rs::Rvalue::BinaryOp(op, box (left, right)) => {
+            let left = translate_operand_value(fcx, point, left)?;
+            let right = translate_operand_value(fcx, point, right)?;
+            match translate_bin_op(*op) {
+                Some(bin_op) => Some(ValueExpr::BinOp(bin_op, GcCow::new(left), GcCow::new(right))),
+                None => rs::span_bug!(span, "unsupported binary operator in translator: {:?}", op),
+            }
+        }
+        rs::Rvalue::UnaryOp(rs::UnOp::Not, op) => {
+            let operand = translate_operand_value(fcx, point, op)?;
+            Some(ValueExpr::UnOp(UnOp::Not, GcCow::new(operand)))
+        }
+        _ => rs::span_bug!(span, "unsupported rvalue in translator: {:?}", rvalue),
+    }
+}
+
+fn translate_operand_value(
+    fcx: &mut FnCtxt<'_, '_>,
+    point: ProgramPoint,
+    op: &rs::Operand<'_>,
+) -> Option<ValueExpr> {
+    match op {
+        rs::Operand::Copy(place) | rs::Operand::Move(place) => {
+            Some(ValueExpr::Load(crate::bb::translate_place(fcx, place)))
+        }
+        rs::Operand::Constant(c) => crate::constant::translate_constant(fcx, point, c),
+    }
+}
+
+fn translate_bin_op(op: rs::BinOp) -> Option<BinOp> {
+    match op {
+        rs::BinOp::Add => Some(BinOp::Int(IntBinOp::Add)),
+        rs::BinOp::Sub => Some(BinOp::Int(IntBinOp::Sub)),
+        rs::BinOp::Mul => Some(BinOp::Int(IntBinOp::Mul)),
+        rs::BinOp::BitAnd => Some(BinOp::Bool(BoolBinOp::BitAnd)),
+        _ => None,
+    }
+}
@@ -0,0 +1,303 @@
+use crate::*;
+
+/// A hand-written substitute for a callee the translator cannot -- or should not -- lower
+/// from its real MIR: compiler intrinsics (`mem::swap`, `ptr::copy`, `size_of`, `transmute`,
+/// ...) and a handful of library entry points that exist to call into the runtime rather than
+/// to be interpreted themselves.
+///
+/// `bb` consults [`lookup_shim`] before attempting to lower a `Call` terminator's callee, and
+/// falls back to normal lowering when no shim matches. A def path is dispatched to exactly one
+/// of the two `Shim` variants below, never both, depending on whether the callee needs its own
+/// control flow (`Body`) or is really just a value computed on the spot (`Inline`).
+pub enum Shim {
+    /// Splice in a hand-written MiniRust function body, as if it were the callee's real MIR.
+    /// The trailing `&[Option<rs::Ty<'_>>]` is the rustc type of each argument, when it was
+    /// cheap to recover -- most shims ignore it, but a few (like `thread::spawn`) need a
+    /// concrete type to resolve a further callee of their own.
+    Body(fn(&mut FnCtxt<'_, '_>, &str, &[ValueExpr], &[Option<rs::Ty<'_>>]) -> Function),
+    /// Perform the call as a direct value computation instead of emitting a `Call`
+    /// terminator -- for intrinsics whose "body" is really a compile-time computation
+    /// (`size_of`, `transmute`, ...). The trailing `rs::GenericArgsRef<'_>` is the callee's own
+    /// monomorphized generics (e.g. the `T` in `size_of::<T>()`), since such intrinsics are
+    /// often generic over a type that never shows up in `args` at all.
+    Inline(fn(&mut FnCtxt<'_, '_>, &str, &[ValueExpr], rs::GenericArgsRef<'_>) -> ValueExpr),
+}
+
+/// Looks up a shim for a callee, keyed by its resolved `smir` def path (e.g.
+/// `"core::mem::swap"`). Returns `None` when the callee should be lowered normally.
+pub fn lookup_shim(def_path: &str) -> Option<Shim> {
+    match def_path {
+        "core::mem::swap" => Some(Shim::Body(shim_mem_swap)),
+        "core::ptr::copy" | "core::ptr::copy_nonoverlapping" | "core::intrinsics::copy" => {
+            Some(Shim::Body(shim_ptr_copy))
+        }
+        "core::intrinsics::size_of" | "core::mem::size_of" => Some(Shim::Inline(shim_size_of)),
+        "core::intrinsics::transmute" | "core::mem::transmute" => {
+            Some(Shim::Inline(shim_transmute))
+        }
+        "std::thread::spawn" => Some(Shim::Body(shim_thread_spawn)),
+        "std::thread::JoinHandle::<T>::join" => Some(Shim::Body(shim_thread_join)),
+        _ if atomic_op(def_path).is_some() => Some(Shim::Body(shim_atomic_op)),
+        _ => None,
+    }
+}
+
+/// Atomic intrinsics are keyed by def path, but which operation and memory ordering they use
+/// are encoded in the path itself (`atomic_load_seqcst`, `atomic_store_relaxed`, ...) rather
+/// than being separate arguments, so callers that need them go through this helper instead of
+/// matching the path directly.
+fn atomic_op(def_path: &str) -> Option<(&'static str, AtomicOrdering)> {
+    let op = ["atomic_load", "atomic_store", "atomic_fence"]
+        .into_iter()
+        .find(|op| def_path.starts_with(&format!("core::intrinsics::{op}")))?;
+    let ordering = match def_path.rsplit('_').next()? {
+        "relaxed" => AtomicOrdering::Relaxed,
+        "acquire" => AtomicOrdering::Acquire,
+        "release" => AtomicOrdering::Release,
+        "acqrel" => AtomicOrdering::AcqRel,
+        "seqcst" => AtomicOrdering::SeqCst,
+        _ => return None,
+    };
+    Some((op, ordering))
+}
+
+/// Recovers `T` from a `&T` / `&mut T` / `*const T` / `*mut T` rustc type -- shims that operate
+/// through a pointer argument (`mem::swap`, the atomic intrinsics, ...) need the concrete value
+/// type behind it, not the pointer type itself.
+fn pointee_ty<'tcx>(ty: rs::Ty<'tcx>) -> rs::Ty<'tcx> {
+    match ty.kind() {
+        rs::TyKind::Ref(_, ty, _) => *ty,
+        rs::TyKind::RawPtr(ty, _) => *ty,
+        _ => panic!("shim expects a pointer or reference argument, found {ty:?}"),
+    }
+}
+
+fn shim_mem_swap(
+    _fcx: &mut FnCtxt<'_, '_>,
+    _def_path: &str,
+    args: &[ValueExpr],
+    arg_types: &[Option<rs::Ty<'_>>],
+) -> Function {
+    let [a, b] = args else { panic!("mem::swap shim expects exactly two arguments") };
+    let [a_ty, _] = arg_types else { panic!("mem::swap shim expects exactly two arguments") };
+    let a_ty = a_ty.unwrap_or_else(|| panic!("mem::swap shim needs the pointee's concrete type"));
+    let value_ty = translate_ty(pointee_ty(a_ty));
+
+    // `a` and `b` are two unrelated addresses -- there's no single location a pair of pointers
+    // derefs into, so go through a temporary instead, the way a hand-written swap would:
+    // `tmp = *a; *a = *b; *b = tmp;`.
+    let tmp = LocalName(Int::ZERO);
+    let a_place = PlaceExpr::Deref(GcCow::new(a.clone()));
+    let b_place = PlaceExpr::Deref(GcCow::new(b.clone()));
+    let statements = List::from_iter([
+        Statement::Assign { destination: PlaceExpr::Local(tmp), source: ValueExpr::Load(a_place.clone()) },
+        Statement::Assign { destination: a_place.clone(), source: ValueExpr::Load(b_place.clone()) },
+        Statement::Assign { destination: b_place, source: ValueExpr::Load(PlaceExpr::Local(tmp)) },
+    ]);
+
+    single_block_function(
+        Map::from_iter([(tmp, value_ty)]),
+        None,
+        BasicBlock { statements, terminator: Terminator::Return },
+    )
+}
+
+fn shim_ptr_copy(
+    _fcx: &mut FnCtxt<'_, '_>,
+    _def_path: &str,
+    args: &[ValueExpr],
+    _arg_types: &[Option<rs::Ty<'_>>],
+) -> Function {
+    intrinsic_call(Intrinsic::Memcpy, args, None)
+}
+
+fn shim_size_of(
+    fcx: &mut FnCtxt<'_, '_>,
+    _def_path: &str,
+    _args: &[ValueExpr],
+    generic_args: rs::GenericArgsRef<'_>,
+) -> ValueExpr {
+    // `size_of::<T>()` takes no value arguments at all -- `T` only ever shows up in the
+    // callee's own generics, which `bb` resolves and passes down here instead of in `args`.
+    let ty = generic_args.type_at(0);
+    let layout = fcx
+        .ctx
+        .tcx
+        .layout_of(rs::ParamEnv::reveal_all().and(ty))
+        .unwrap_or_else(|err| panic!("size_of shim could not lay out {ty:?}: {err:?}"));
+    ValueExpr::Constant(Constant::Int(Int::from(layout.size.bytes())), <usize>::get_type())
+}
+
+fn shim_transmute(
+    _fcx: &mut FnCtxt<'_, '_>,
+    _def_path: &str,
+    args: &[ValueExpr],
+    _generic_args: rs::GenericArgsRef<'_>,
+) -> ValueExpr {
+    let [value] = args else { panic!("transmute shim expects exactly one argument") };
+    // A transmute is a no-op at the `ValueExpr` level: the translator only needs to reinterpret
+    // the type the value is tagged with, not its bytes.
+    value.clone()
+}
+
+/// `std::thread::spawn(closure)` becomes a single `Intrinsic::Spawn`, with the spawned
+/// thread's entry point resolved to the closure's `FnOnce::call_once` shim and the closure
+/// itself passed along as that entry point's argument. `Spawn` hands back the new thread's
+/// `JoinHandle`, represented here as the opaque `usize` thread id `join` is later called with.
+fn shim_thread_spawn(
+    fcx: &mut FnCtxt<'_, '_>,
+    _def_path: &str,
+    args: &[ValueExpr],
+    arg_types: &[Option<rs::Ty<'_>>],
+) -> Function {
+    let [closure] = args else { panic!("thread::spawn shim expects exactly one argument") };
+    let [closure_ty] = arg_types else { panic!("thread::spawn shim expects exactly one argument") };
+    let closure_ty = closure_ty
+        .unwrap_or_else(|| panic!("thread::spawn shim needs the closure's concrete type"));
+    let rs::TyKind::Closure(def_id, substs) = closure_ty.kind() else {
+        panic!("thread::spawn shim expects a closure argument, found {closure_ty:?}")
+    };
+    let entry_point =
+        rs::Instance::resolve_closure(fcx.ctx.tcx, *def_id, substs, rs::ClosureKind::FnOnce);
+    let entry_point = fcx.ctx.translate_instance(entry_point);
+    let entry_point_value =
+        ValueExpr::Constant(Constant::FnPointer(entry_point), Type::Ptr(PtrType::FnPtr));
+    intrinsic_call(
+        Intrinsic::Spawn,
+        &[entry_point_value, closure.clone()],
+        Some(<usize>::get_type()),
+    )
+}
+
+/// `JoinHandle::<T>::join` becomes an `Intrinsic::Join` on the handle argument, blocking the
+/// calling thread until the spawned one has terminated and handing back the value it produced.
+/// The real `join` returns `Result<T, Box<dyn Any + Send>>`, but the translator doesn't model
+/// `Result` or `Box<dyn Any>` (a spawned thread panicking isn't represented at all), so this
+/// shim yields `T` directly, the same simplification `shim_thread_spawn` already makes by
+/// treating `JoinHandle` as an opaque `usize` rather than a real value.
+fn shim_thread_join(
+    _fcx: &mut FnCtxt<'_, '_>,
+    _def_path: &str,
+    args: &[ValueExpr],
+    arg_types: &[Option<rs::Ty<'_>>],
+) -> Function {
+    let [handle_ty, ..] = arg_types else { panic!("JoinHandle::join shim expects an argument") };
+    let handle_ty =
+        handle_ty.unwrap_or_else(|| panic!("JoinHandle::join shim needs the handle's concrete type"));
+    let rs::TyKind::Adt(_, substs) = handle_ty.kind() else {
+        panic!("JoinHandle::join shim expects a `JoinHandle<T>` argument, found {handle_ty:?}")
+    };
+    let ret_ty = translate_ty(substs.type_at(0));
+    intrinsic_call(Intrinsic::Join, args, Some(ret_ty))
+}
+
+/// Lowers any of the `atomic_{load,store,fence}_<ordering>` intrinsics to the matching
+/// MiniRust atomic memory-access intrinsic. The interpreter is where two such accesses to the
+/// same location (with at least one a write) get reported as a data race.
+fn shim_atomic_op(
+    _fcx: &mut FnCtxt<'_, '_>,
+    def_path: &str,
+    args: &[ValueExpr],
+    arg_types: &[Option<rs::Ty<'_>>],
+) -> Function {
+    let (op, ordering) =
+        atomic_op(def_path).expect("lookup_shim only selects this shim for atomic intrinsics");
+    let intrinsic = match op {
+        "atomic_load" => Intrinsic::AtomicLoad(ordering),
+        "atomic_store" => Intrinsic::AtomicStore(ordering),
+        "atomic_fence" => Intrinsic::AtomicFence(ordering),
+        _ => unreachable!(),
+    };
+    // Only a load hands a value back to the caller; a store/fence's `args` are fully consumed
+    // by the intrinsic itself.
+    let ret_ty = match op {
+        "atomic_load" => {
+            let [ptr_ty, ..] = arg_types else {
+                panic!("atomic_load shim expects at least one argument")
+            };
+            let ptr_ty = ptr_ty
+                .unwrap_or_else(|| panic!("atomic_load shim needs the pointee's concrete type"));
+            Some(translate_ty(pointee_ty(ptr_ty)))
+        }
+        _ => None,
+    };
+    intrinsic_call(intrinsic, args, ret_ty)
+}
+
+/// Runs `intrinsic` in a fresh shim function and hands control back to the caller afterwards,
+/// the same way an ordinary `Call`'s `ret_place`/`next_block` get resumed: the `Intrinsic`
+/// terminator's own `next_block` leads to a second block that just returns, and -- when
+/// `ret_ty` is `Some` -- the intrinsic's result is written into a declared return local first.
+fn intrinsic_call(intrinsic: Intrinsic, args: &[ValueExpr], ret_ty: Option<Type>) -> Function {
+    let ret_block = BbName(Int::from(1));
+    let (locals, ret, ret_place) = match ret_ty {
+        Some(ty) => {
+            let ret = LocalName(Int::ZERO);
+            (Map::from_iter([(ret, ty)]), Some(ret), Some(PlaceExpr::Local(ret)))
+        }
+        None => (Map::default(), None, None),
+    };
+
+    let blocks = Map::from_iter([
+        (
+            BbName(Int::ZERO),
+            BasicBlock {
+                statements: List::default(),
+                terminator: Terminator::Intrinsic {
+                    intrinsic,
+                    arguments: List::from_iter(args.iter().cloned()),
+                    ret: ret_place,
+                    next_block: Some(ret_block),
+                },
+            },
+        ),
+        (ret_block, BasicBlock { statements: List::default(), terminator: Terminator::Return }),
+    ]);
+
+    Function { locals, args: List::default(), ret, blocks, start: BbName(Int::ZERO) }
+}
+
+fn single_block_function(
+    locals: Map<LocalName, Type>,
+    ret: Option<LocalName>,
+    block: BasicBlock,
+) -> Function {
+    Function {
+        locals,
+        args: List::default(),
+        ret,
+        blocks: Map::from_iter([(BbName(Int::ZERO), block)]),
+        start: BbName(Int::ZERO),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn atomic_op_parses_operation_and_ordering() {
+        assert_eq!(
+            atomic_op("core::intrinsics::atomic_load_seqcst"),
+            Some(("atomic_load", AtomicOrdering::SeqCst))
+        );
+        assert_eq!(
+            atomic_op("core::intrinsics::atomic_store_relaxed"),
+            Some(("atomic_store", AtomicOrdering::Relaxed))
+        );
+        assert_eq!(
+            atomic_op("core::intrinsics::atomic_fence_acqrel"),
+            Some(("atomic_fence", AtomicOrdering::AcqRel))
+        );
+    }
+
+    #[test]
+    fn atomic_op_rejects_non_atomic_paths() {
+        assert_eq!(atomic_op("core::mem::swap"), None);
+    }
+
+    #[test]
+    fn atomic_op_rejects_an_unknown_ordering() {
+        assert_eq!(atomic_op("core::intrinsics::atomic_load_bogus"), None);
+    }
+}
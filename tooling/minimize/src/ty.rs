@@ -0,0 +1,25 @@
+use crate::*;
+
+/// Translates a monomorphized rustc `Ty` into the MiniRust `Type` it's represented by.
+///
+/// Only the handful of scalar types the translator currently lowers anything of are handled;
+/// everything else is a translator limitation, not a property of the input program, so it panics
+/// rather than pretending to produce a real type for it.
+pub fn translate_ty(ty: rs::Ty<'_>) -> Type {
+    match ty.kind() {
+        rs::TyKind::Bool => <bool>::get_type(),
+        rs::TyKind::Int(rs::IntTy::I8) => <i8>::get_type(),
+        rs::TyKind::Int(rs::IntTy::I16) => <i16>::get_type(),
+        rs::TyKind::Int(rs::IntTy::I32) => <i32>::get_type(),
+        rs::TyKind::Int(rs::IntTy::I64) => <i64>::get_type(),
+        rs::TyKind::Int(rs::IntTy::I128) => <i128>::get_type(),
+        rs::TyKind::Int(rs::IntTy::Isize) => <isize>::get_type(),
+        rs::TyKind::Uint(rs::UintTy::U8) => <u8>::get_type(),
+        rs::TyKind::Uint(rs::UintTy::U16) => <u16>::get_type(),
+        rs::TyKind::Uint(rs::UintTy::U32) => <u32>::get_type(),
+        rs::TyKind::Uint(rs::UintTy::U64) => <u64>::get_type(),
+        rs::TyKind::Uint(rs::UintTy::U128) => <u128>::get_type(),
+        rs::TyKind::Uint(rs::UintTy::Usize) => <usize>::get_type(),
+        _ => panic!("unsupported type in translator: {ty:?}"),
+    }
+}
@@ -0,0 +1,7 @@
+//@ run-pass
+
+fn main() {
+    let a: u32 = 2;
+    let b: u32 = 3;
+    let _c = a * b + a;
+}
@@ -0,0 +1,6 @@
+//@ run-pass
+
+fn main() {
+    let x: u32 = const { 2 + 3 };
+    let _ = x;
+}
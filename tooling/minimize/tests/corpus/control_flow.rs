@@ -0,0 +1,13 @@
+//@ run-pass
+
+fn select(cond: bool, a: u32, b: u32) -> u32 {
+    if cond {
+        a
+    } else {
+        b
+    }
+}
+
+fn main() {
+    let _ = select(true, 1, 2);
+}
@@ -0,0 +1,6 @@
+//@ run-pass
+
+fn main() {
+    let n: usize = core::mem::size_of::<u32>();
+    let _ = n;
+}